@@ -0,0 +1,177 @@
+//! channel history backfill
+//!
+//! pages backward through a channel's message history via `before` cursors
+//! and feeds each message into the index, persisting the cursor to disk so
+//! an interrupted backfill can resume where it left off
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serenity::builder::CreateApplicationCommand;
+use serenity::model::prelude::command::CommandOptionType;
+use serenity::model::prelude::interaction::application_command::{
+    ApplicationCommandInteraction, CommandDataOptionValue,
+};
+use serenity::model::prelude::interaction::InteractionResponseType;
+use serenity::model::prelude::{ChannelId, GuildId, MessageId, Permissions};
+use serenity::prelude::Context;
+use tantivy::schema::Term;
+
+use crate::data::shared_state;
+use crate::extract;
+use crate::indexer::SharedIndex;
+use crate::schema::MessageSchema;
+
+pub const COMMAND_NAME: &str = "backfill";
+
+const PAGE_SIZE: u64 = 100;
+/// pause between pages so a large backfill doesn't hammer the REST API
+const PAGE_DELAY: Duration = Duration::from_millis(1200);
+/// where resumable cursors are persisted, one file per channel
+const CURSOR_DIR: &str = "./backfill";
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command
+        .name(COMMAND_NAME)
+        .description("Backfill the search index with a channel's message history")
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .create_option(|option| {
+            option
+                .name("channel")
+                .description("the channel to backfill")
+                .kind(CommandOptionType::Channel)
+                .required(true)
+        })
+}
+
+pub async fn run(ctx: &Context, command: &ApplicationCommandInteraction) {
+    let Some(guild_id) = command.guild_id else {
+        respond(ctx, command, "/backfill only works in a server.").await;
+        return;
+    };
+
+    let Some(channel_id) = channel_option(command) else {
+        respond(ctx, command, "Please specify a channel.").await;
+        return;
+    };
+
+    let (Some(schema), Some(index)) = shared_state(ctx).await else {
+        respond(ctx, command, "Search isn't ready yet, try again in a moment.").await;
+        return;
+    };
+
+    respond(
+        ctx,
+        command,
+        &format!("Backfilling <#{channel_id}> in the background..."),
+    )
+    .await;
+
+    let ctx = ctx.clone();
+    tokio::spawn(async move {
+        if let Err(why) = backfill_channel(&ctx, channel_id, guild_id, &schema, &index).await {
+            println!("Backfill of channel {channel_id} failed: {why:?}");
+        }
+    });
+}
+
+fn channel_option(command: &ApplicationCommandInteraction) -> Option<ChannelId> {
+    let option = command.data.options.first()?;
+    match option.resolved.as_ref()? {
+        CommandDataOptionValue::Channel(channel) => Some(channel.id),
+        _ => None,
+    }
+}
+
+async fn backfill_channel(
+    ctx: &Context,
+    channel_id: ChannelId,
+    guild_id: GuildId,
+    schema: &MessageSchema,
+    index: &SharedIndex,
+) -> serenity::Result<()> {
+    let mut before = load_cursor(channel_id);
+
+    loop {
+        let mut page = channel_id
+            .messages(&ctx.http, |retriever| {
+                let retriever = retriever.limit(PAGE_SIZE);
+                match before {
+                    Some(before) => retriever.before(before),
+                    None => retriever,
+                }
+            })
+            .await?;
+
+        if page.is_empty() {
+            break;
+        }
+
+        let extractors = extract::default_extractors();
+        for message in &mut page {
+            // the REST API doesn't set guild_id on fetched messages
+            message.guild_id.get_or_insert(guild_id);
+
+            let mut doc = schema.parse_message(message);
+            extract::extract_into(schema, &extractors, &message.attachments, &mut doc).await;
+
+            // re-running (or resuming) a backfill must not create duplicates
+            let term = Term::from_field_u64(schema.id_field(), message.id.0);
+            index.replace_document(term, doc).await;
+        }
+
+        // messages() returns newest-first, so the last entry is the oldest in the page
+        let oldest = page.last().expect("page was checked non-empty").id;
+        before = Some(oldest);
+        save_cursor(channel_id, oldest);
+
+        if page.len() < PAGE_SIZE as usize {
+            break;
+        }
+
+        tokio::time::sleep(PAGE_DELAY).await;
+    }
+
+    index.commit_if_pending().await;
+    clear_cursor(channel_id);
+
+    Ok(())
+}
+
+fn cursor_path(channel_id: ChannelId) -> PathBuf {
+    PathBuf::from(CURSOR_DIR).join(format!("{}.cursor", channel_id.0))
+}
+
+fn load_cursor(channel_id: ChannelId) -> Option<MessageId> {
+    let contents = fs::read_to_string(cursor_path(channel_id)).ok()?;
+    contents.trim().parse::<u64>().ok().map(MessageId)
+}
+
+fn save_cursor(channel_id: ChannelId, message_id: MessageId) {
+    if let Err(why) = fs::create_dir_all(CURSOR_DIR) {
+        println!("Failed to create backfill cursor directory: {why:?}");
+        return;
+    }
+
+    if let Err(why) = fs::write(cursor_path(channel_id), message_id.0.to_string()) {
+        println!("Failed to persist backfill cursor: {why:?}");
+    }
+}
+
+fn clear_cursor(channel_id: ChannelId) {
+    let _ = fs::remove_file(cursor_path(channel_id));
+}
+
+async fn respond(ctx: &Context, command: &ApplicationCommandInteraction, content: &str) {
+    if let Err(why) = command
+        .create_interaction_response(&ctx.http, |response| {
+            response
+                .kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|message| message.content(content))
+        })
+        .await
+    {
+        println!("Failed to respond to /backfill: {why:?}");
+    }
+}
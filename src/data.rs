@@ -0,0 +1,29 @@
+//! shared state stored in serenity's `TypeMap` (`ctx.data`)
+
+use std::sync::Arc;
+
+use serenity::prelude::{Context, TypeMapKey};
+
+use crate::indexer::SharedIndex;
+use crate::schema::MessageSchema;
+
+pub struct IndexKey;
+
+impl TypeMapKey for IndexKey {
+    type Value = Arc<SharedIndex>;
+}
+
+pub struct SchemaKey;
+
+impl TypeMapKey for SchemaKey {
+    type Value = Arc<MessageSchema>;
+}
+
+/// fetches the schema and index out of the shared `TypeMap`, if `ready` has run
+pub async fn shared_state(ctx: &Context) -> (Option<Arc<MessageSchema>>, Option<Arc<SharedIndex>>) {
+    let data = ctx.data.read().await;
+    (
+        data.get::<SchemaKey>().cloned(),
+        data.get::<IndexKey>().cloned(),
+    )
+}
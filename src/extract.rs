@@ -0,0 +1,76 @@
+//! pluggable text extraction for attachments
+//!
+//! `parse_message` only records filenames and content types; this fills in
+//! the `attachment_content` field so attachments are findable by what's
+//! actually in the file, not just its name. only `text/*` attachments have
+//! an extractor; PDFs and other types are findable by filename and content
+//! type only
+
+use serenity::async_trait;
+use serenity::model::channel::Attachment;
+use tantivy::Document;
+
+use crate::schema::MessageSchema;
+
+/// attachments larger than this are skipped — extraction runs inline on the
+/// gateway task and shouldn't block on downloading something huge
+const MAX_EXTRACTABLE_BYTES: u64 = 1_000_000;
+
+#[async_trait]
+pub trait AttachmentExtractor: Send + Sync {
+    /// whether this extractor knows how to handle the given attachment
+    fn supports(&self, attachment: &Attachment) -> bool;
+
+    /// pull out the searchable text, if any
+    async fn extract(&self, attachment: &Attachment) -> Option<String>;
+}
+
+/// handles `text/*` attachments (plain text, markdown) by decoding them as UTF-8
+pub struct PlainTextExtractor;
+
+#[async_trait]
+impl AttachmentExtractor for PlainTextExtractor {
+    fn supports(&self, attachment: &Attachment) -> bool {
+        attachment
+            .content_type
+            .as_deref()
+            .is_some_and(|content_type| content_type.starts_with("text/"))
+    }
+
+    async fn extract(&self, attachment: &Attachment) -> Option<String> {
+        if attachment.size > MAX_EXTRACTABLE_BYTES {
+            return None;
+        }
+
+        let bytes = attachment.download().await.ok()?;
+        String::from_utf8(bytes).ok()
+    }
+}
+
+/// the default extractor pipeline
+///
+/// add a PDF extractor here once a PDF-parsing dependency is pulled in; it
+/// only needs to implement `AttachmentExtractor` to slot in
+pub fn default_extractors() -> Vec<Box<dyn AttachmentExtractor>> {
+    vec![Box::new(PlainTextExtractor)]
+}
+
+/// for each of a message's attachments, runs the first extractor that
+/// supports it, adding whatever text it finds into `doc`'s
+/// `attachment_content` field
+pub async fn extract_into(
+    schema: &MessageSchema,
+    extractors: &[Box<dyn AttachmentExtractor>],
+    attachments: &[Attachment],
+    doc: &mut Document,
+) {
+    for attachment in attachments {
+        let Some(extractor) = extractors.iter().find(|e| e.supports(attachment)) else {
+            continue;
+        };
+
+        if let Some(text) = extractor.extract(attachment).await {
+            doc.add_text(schema.attachment_content_field(), text);
+        }
+    }
+}
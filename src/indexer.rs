@@ -0,0 +1,110 @@
+//! the shared `IndexWriter` and the background task that keeps it committed
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::Duration;
+
+use serenity::prelude::Context;
+use tantivy::schema::Term;
+use tantivy::{Document, Index, IndexReader, IndexWriter, ReloadPolicy, Searcher};
+use tokio::sync::Mutex;
+
+use crate::data::IndexKey;
+
+/// pending documents that trigger an eager commit without waiting for the timer
+const COMMIT_DOC_THRESHOLD: usize = 50;
+/// upper bound on how long freshly indexed messages stay unsearchable
+const COMMIT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// wraps an `IndexWriter` (for writes) and an `IndexReader` (for search) so
+/// callers don't have to worry about when the writer actually gets committed
+pub struct SharedIndex {
+    writer: Mutex<IndexWriter>,
+    reader: IndexReader,
+    pending: AtomicUsize,
+}
+
+impl SharedIndex {
+    pub fn new(index: &Index, writer: IndexWriter) -> Self {
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommit)
+            .try_into()
+            .expect("Failed to build index reader");
+
+        Self {
+            writer: Mutex::new(writer),
+            reader,
+            pending: AtomicUsize::new(0),
+        }
+    }
+
+    /// a point-in-time snapshot of the index to search against
+    pub fn searcher(&self) -> Searcher {
+        self.reader.searcher()
+    }
+
+    /// add a document, committing immediately once `COMMIT_DOC_THRESHOLD` is reached
+    pub async fn add_document(&self, doc: Document) {
+        let mut writer = self.writer.lock().await;
+        writer
+            .add_document(doc)
+            .expect("Failed to add document to index");
+        self.bump_pending(&mut writer);
+    }
+
+    /// delete the document matching `term` (e.g. the `id` term for a deleted message)
+    pub async fn delete_document(&self, term: Term) {
+        let mut writer = self.writer.lock().await;
+        writer.delete_term(term);
+        self.bump_pending(&mut writer);
+    }
+
+    /// delete the document matching `term`, then add `doc` in its place (an edit)
+    pub async fn replace_document(&self, term: Term, doc: Document) {
+        let mut writer = self.writer.lock().await;
+        writer.delete_term(term);
+        writer
+            .add_document(doc)
+            .expect("Failed to add document to index");
+        self.bump_pending(&mut writer);
+    }
+
+    /// commit whatever is pending, regardless of the batch threshold
+    pub async fn commit_if_pending(&self) {
+        if self.pending.swap(0, Ordering::SeqCst) == 0 {
+            return;
+        }
+
+        let mut writer = self.writer.lock().await;
+        writer.commit().expect("Failed to commit index");
+    }
+
+    fn bump_pending(&self, writer: &mut IndexWriter) {
+        if self.pending.fetch_add(1, Ordering::SeqCst) + 1 >= COMMIT_DOC_THRESHOLD {
+            writer.commit().expect("Failed to commit index");
+            self.pending.store(0, Ordering::SeqCst);
+        }
+    }
+}
+
+/// spawns the background task that flushes pending writes every `COMMIT_INTERVAL`
+pub fn spawn_commit_task(ctx: Context) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(COMMIT_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let index = {
+                let data = ctx.data.read().await;
+                data.get::<IndexKey>().cloned()
+            };
+
+            if let Some(index) = index {
+                index.commit_if_pending().await;
+            }
+        }
+    });
+}
@@ -1,12 +1,33 @@
+mod backfill;
+mod data;
+mod extract;
+mod indexer;
+mod query;
 mod schema;
+mod search;
+mod stats;
 
 extern crate dotenv;
 
-use dotenv::dotenv;
-use serenity::{async_trait, model::gateway::Ready, prelude::*};
+use std::sync::Arc;
 use std::{env, fs, path::Path};
+
+use dotenv::dotenv;
+use serenity::{
+    async_trait,
+    model::application::command::Command,
+    model::application::interaction::Interaction,
+    model::channel::Message,
+    model::event::MessageUpdateEvent,
+    model::gateway::Ready,
+    model::id::{ChannelId, GuildId, MessageId},
+    prelude::*,
+};
+use tantivy::schema::Term;
 use tantivy::{directory::MmapDirectory, schema::Schema, Index};
 
+use crate::data::{shared_state, IndexKey, SchemaKey};
+use crate::indexer::SharedIndex;
 use crate::schema::MessageSchema;
 
 fn open_index<P>(path: P, schema: Schema) -> Index
@@ -27,6 +48,121 @@ impl EventHandler for Handler {
 
         let schema = MessageSchema::build();
         let index = open_index("./index", schema.clone_inner());
+        let writer = index
+            .writer(50_000_000)
+            .expect("Failed to create index writer");
+
+        {
+            let mut data = ctx.data.write().await;
+            data.insert::<IndexKey>(Arc::new(SharedIndex::new(&index, writer)));
+            data.insert::<SchemaKey>(Arc::new(schema));
+        }
+
+        if let Err(why) = Command::set_global_application_commands(&ctx.http, |commands| {
+            commands
+                .create_application_command(search::register)
+                .create_application_command(backfill::register)
+                .create_application_command(stats::register)
+        })
+        .await
+        {
+            println!("Failed to register application commands: {why:?}");
+        }
+
+        indexer::spawn_commit_task(ctx);
+    }
+
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        if let Interaction::ApplicationCommand(command) = interaction {
+            match command.data.name.as_str() {
+                search::COMMAND_NAME => search::run(&ctx, &command).await,
+                backfill::COMMAND_NAME => backfill::run(&ctx, &command).await,
+                stats::COMMAND_NAME => stats::run(&ctx, &command).await,
+                _ => {}
+            }
+        }
+    }
+
+    async fn message(&self, ctx: Context, new_message: Message) {
+        let (Some(schema), Some(index)) = shared_state(&ctx).await else {
+            return;
+        };
+
+        let mut doc = schema.parse_message(&new_message);
+        extract::extract_into(
+            &schema,
+            &extract::default_extractors(),
+            &new_message.attachments,
+            &mut doc,
+        )
+        .await;
+        index.add_document(doc).await;
+    }
+
+    async fn message_update(
+        &self,
+        ctx: Context,
+        _old_if_available: Option<Message>,
+        new: Option<Message>,
+        event: MessageUpdateEvent,
+    ) {
+        let (Some(schema), Some(index)) = shared_state(&ctx).await else {
+            return;
+        };
+
+        let message = match new {
+            Some(message) => message,
+            None => match ctx.http.get_message(event.channel_id.0, event.id.0).await {
+                Ok(message) => message,
+                Err(why) => {
+                    println!("Failed to fetch edited message {}: {why:?}", event.id);
+                    return;
+                }
+            },
+        };
+
+        let term = Term::from_field_u64(schema.id_field(), message.id.0);
+        let mut doc = schema.parse_message(&message);
+        extract::extract_into(
+            &schema,
+            &extract::default_extractors(),
+            &message.attachments,
+            &mut doc,
+        )
+        .await;
+        index.replace_document(term, doc).await;
+    }
+
+    async fn message_delete(
+        &self,
+        ctx: Context,
+        _channel_id: ChannelId,
+        message_id: MessageId,
+        _guild_id: Option<GuildId>,
+    ) {
+        let (Some(schema), Some(index)) = shared_state(&ctx).await else {
+            return;
+        };
+
+        let term = Term::from_field_u64(schema.id_field(), message_id.0);
+        index.delete_document(term).await;
+    }
+
+    async fn message_delete_bulk(
+        &self,
+        ctx: Context,
+        _channel_id: ChannelId,
+        message_ids: Vec<MessageId>,
+        _guild_id: Option<GuildId>,
+    ) {
+        let (Some(schema), Some(index)) = shared_state(&ctx).await else {
+            return;
+        };
+
+        for message_id in message_ids {
+            let term = Term::from_field_u64(schema.id_field(), message_id.0);
+            index.delete_document(term).await;
+        }
     }
 }
 
@@ -37,8 +173,12 @@ async fn main() {
     let token = env::var("DISCORD_TOKEN").expect("Expected a token in the environment");
 
     // create client
-    let intents =
-        GatewayIntents::GUILDS | GatewayIntents::GUILD_MESSAGES | GatewayIntents::MESSAGE_CONTENT;
+    // GUILD_MEMBERS is needed so permissions_for_user can resolve an
+    // arbitrary searcher's roles from the cache when filtering results
+    let intents = GatewayIntents::GUILDS
+        | GatewayIntents::GUILD_MEMBERS
+        | GatewayIntents::GUILD_MESSAGES
+        | GatewayIntents::MESSAGE_CONTENT;
     let mut client = Client::builder(token, intents)
         .event_handler(Handler)
         .await
@@ -0,0 +1,128 @@
+//! parses the `/search` query language into a Tantivy query
+//!
+//! supports free text (matched against `content`/`embed_content`/
+//! `attachment_filename`/`attachment_content`) plus the field filters
+//! `from:`, `in:`, `has:`, `mime:`, `pinned:`, `mentions:`, `before:` and
+//! `after:`
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use strum::IntoEnumIterator;
+use tantivy::query::{BooleanQuery, Occur, Query, QueryParser, RangeQuery, TermQuery};
+use tantivy::schema::{Field, IndexRecordOption, Term};
+use tantivy::tokenizer::TokenizerManager;
+use tantivy::DateTime;
+
+use crate::schema::{MessageMediaType, MessageSchema};
+
+/// always scopes the query to `guild_id`, so results (and the jump links built
+/// from them) only ever come from the server the command was invoked in
+pub fn parse_query(schema: &MessageSchema, guild_id: u64, input: &str) -> Box<dyn Query> {
+    let mut clauses: Vec<(Occur, Box<dyn Query>)> =
+        vec![(Occur::Must, term_query_u64(schema.guild_id_field(), guild_id))];
+    let mut free_text_terms = Vec::new();
+
+    for token in input.split_whitespace() {
+        match parse_filter(schema, token) {
+            Some(filter) => clauses.push((Occur::Must, filter)),
+            None => free_text_terms.push(token),
+        }
+    }
+
+    if !free_text_terms.is_empty() {
+        let parser = QueryParser::new(
+            schema.clone_inner(),
+            vec![
+                schema.content_field(),
+                schema.embed_content_field(),
+                schema.attachment_filename_field(),
+                schema.attachment_content_field(),
+            ],
+            TokenizerManager::default(),
+        );
+
+        if let Ok(free_text_query) = parser.parse_query(&free_text_terms.join(" ")) {
+            clauses.push((Occur::Must, free_text_query));
+        }
+    }
+
+    Box::new(BooleanQuery::new(clauses))
+}
+
+fn parse_filter(schema: &MessageSchema, token: &str) -> Option<Box<dyn Query>> {
+    let (key, value) = token.split_once(':')?;
+
+    match key {
+        "from" => Some(term_query_u64(
+            schema.author_id_field(),
+            parse_mention(value, '@')?,
+        )),
+        "in" => Some(term_query_u64(
+            schema.channel_id_field(),
+            parse_mention(value, '#')?,
+        )),
+        "mentions" => Some(term_query_u64(
+            schema.mention_user_id_field(),
+            parse_mention(value, '@')?,
+        )),
+        "has" => {
+            let media_type = MessageMediaType::iter().find(|m| m.to_string() == value)?;
+            let term = Term::from_field_text(schema.has_field(), &media_type.to_string());
+            Some(Box::new(TermQuery::new(term, IndexRecordOption::Basic)))
+        }
+        // precise MIME filter over attachment_content_type, e.g. mime:application/pdf
+        "mime" => {
+            let term = Term::from_field_text(schema.attachment_content_type_field(), value);
+            Some(Box::new(TermQuery::new(term, IndexRecordOption::Basic)))
+        }
+        "pinned" => {
+            let pinned: bool = value.parse().ok()?;
+            let term = Term::from_field_bool(schema.pinned_field(), pinned);
+            Some(Box::new(TermQuery::new(term, IndexRecordOption::Basic)))
+        }
+        // start the range at 0 so "before" really means "any time up to here"
+        "before" => {
+            let end: i64 = value.parse().ok()?;
+            Some(timestamp_range_query(schema, 0, end))
+        }
+        // end the range at the current time so "after" really means "up to now"
+        "after" => {
+            let start: i64 = value.parse().ok()?;
+            Some(timestamp_range_query(schema, start, now_unix()))
+        }
+        _ => None,
+    }
+}
+
+/// accepts a raw snowflake, or a Discord mention like `<@123>` or `<#123>`
+fn parse_mention(value: &str, sigil: char) -> Option<u64> {
+    let trimmed = value
+        .strip_prefix('<')
+        .and_then(|s| s.strip_prefix(sigil))
+        .and_then(|s| s.strip_suffix('>'))
+        .unwrap_or(value);
+
+    trimmed.trim_start_matches('!').parse().ok()
+}
+
+pub(crate) fn term_query_u64(field: Field, value: u64) -> Box<dyn Query> {
+    Box::new(TermQuery::new(
+        Term::from_field_u64(field, value),
+        IndexRecordOption::Basic,
+    ))
+}
+
+// `end` is exclusive, so nudge it a second later to make both bounds inclusive
+pub(crate) fn timestamp_range_query(schema: &MessageSchema, start: i64, end: i64) -> Box<dyn Query> {
+    Box::new(RangeQuery::new_date(
+        schema.timestamp_field(),
+        DateTime::from_timestamp_secs(start)..DateTime::from_timestamp_secs(end + 1),
+    ))
+}
+
+pub(crate) fn now_unix() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system clock is before the unix epoch")
+        .as_secs() as i64
+}
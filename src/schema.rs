@@ -52,6 +52,8 @@ pub struct MessageSchema {
     id: Field,
     author_id: Field,
     channel_id: Field,
+    /// guild the message was sent in, so queries can be scoped to one server
+    guild_id: Field,
 
     /// main message text
     content: Field,
@@ -67,6 +69,14 @@ pub struct MessageSchema {
     mention_role_id: Field,
     /// media type(s) in the message (link, image, etc; may be multi-valued)
     has: Field,
+    /// hierarchical facet mirror of `has` (e.g. `/has/image`), for aggregate counts
+    has_facet: Field,
+    /// filenames of any attachments (may be multi-valued)
+    attachment_filename: Field,
+    /// MIME content types of any attachments (may be multi-valued)
+    attachment_content_type: Field,
+    /// extracted text content of any attachments, where available (may be multi-valued)
+    attachment_content: Field,
 
     inner: Schema,
 }
@@ -76,12 +86,14 @@ impl MessageSchema {
         let mut schema_builder = Schema::builder();
 
         Self {
-            // never need to search by message id, you can just use a message link for that
-            id: schema_builder.add_u64_field("id", STORED),
+            // indexed so edits/deletes can be applied as a delete_term on this field
+            id: schema_builder.add_u64_field("id", INDEXED | STORED),
             author_id: schema_builder.add_u64_field("author_id", INDEXED),
             // fast so we can filter by the user's permissions
-            // also need this for putting a channel mention in the output message
-            channel_id: schema_builder.add_u64_field("channel_id", INDEXED | FAST),
+            // stored so results can build a channel mention and a jump link
+            channel_id: schema_builder.add_u64_field("channel_id", INDEXED | FAST | STORED),
+            // indexed so every cross-message query can be scoped to a single guild
+            guild_id: schema_builder.add_u64_field("guild_id", INDEXED),
 
             // stored so we can display it in the output message
             content: schema_builder.add_text_field("content", TEXT | STORED),
@@ -97,6 +109,16 @@ impl MessageSchema {
             // string instead of text because this comes from the MessageMediaType enum
             // it's always a single word so no need to tokenize it
             has: schema_builder.add_text_field("has", STRING),
+            // mirrors `has`, but as a facet so /stats can count media types with FacetCollector
+            has_facet: schema_builder.add_facet_field("has_facet", INDEXED),
+
+            // tokenized so "report.pdf" is findable by "report"
+            attachment_filename: schema_builder.add_text_field("attachment_filename", TEXT),
+            // string instead of text so has:-style exact MIME filtering works
+            attachment_content_type: schema_builder
+                .add_text_field("attachment_content_type", STRING),
+            // populated by a pluggable text-extraction step, not by parse_message itself
+            attachment_content: schema_builder.add_text_field("attachment_content", TEXT),
 
             // build the schema LAST
             inner: schema_builder.build(),
@@ -109,6 +131,7 @@ impl MessageSchema {
             self.id => message.id.0,
             self.author_id => message.author.id.0,
             self.channel_id => message.channel_id.0,
+            self.guild_id => message.guild_id.map_or(0, |id| id.0),
 
             self.content => message.content.clone(),
             self.timestamp => DateTime::from_timestamp_secs(message.timestamp.unix_timestamp()),
@@ -138,12 +161,80 @@ impl MessageSchema {
         for media_type in MessageMediaType::iter() {
             if media_type.is_in_message(message) {
                 doc.add_text(self.has, media_type.to_string());
+                doc.add_facet(self.has_facet, Facet::from(format!("/has/{media_type}")));
+            }
+        }
+
+        for attachment in &message.attachments {
+            doc.add_text(self.attachment_filename, &attachment.filename);
+            if let Some(content_type) = &attachment.content_type {
+                doc.add_text(self.attachment_content_type, content_type);
             }
         }
 
         doc
     }
 
+    pub fn id_field(&self) -> Field {
+        self.id
+    }
+
+    pub fn author_id_field(&self) -> Field {
+        self.author_id
+    }
+
+    pub fn channel_id_field(&self) -> Field {
+        self.channel_id
+    }
+
+    pub fn guild_id_field(&self) -> Field {
+        self.guild_id
+    }
+
+    pub fn content_field(&self) -> Field {
+        self.content
+    }
+
+    pub fn embed_content_field(&self) -> Field {
+        self.embed_content
+    }
+
+    pub fn timestamp_field(&self) -> Field {
+        self.timestamp
+    }
+
+    pub fn pinned_field(&self) -> Field {
+        self.pinned
+    }
+
+    pub fn mention_user_id_field(&self) -> Field {
+        self.mention_user_id
+    }
+
+    pub fn mention_role_id_field(&self) -> Field {
+        self.mention_role_id
+    }
+
+    pub fn has_field(&self) -> Field {
+        self.has
+    }
+
+    pub fn has_facet_field(&self) -> Field {
+        self.has_facet
+    }
+
+    pub fn attachment_filename_field(&self) -> Field {
+        self.attachment_filename
+    }
+
+    pub fn attachment_content_type_field(&self) -> Field {
+        self.attachment_content_type
+    }
+
+    pub fn attachment_content_field(&self) -> Field {
+        self.attachment_content
+    }
+
     pub fn inner(&self) -> &Schema {
         &self.inner
     }
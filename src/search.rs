@@ -0,0 +1,183 @@
+//! the `/search` slash command
+
+use serenity::builder::CreateApplicationCommand;
+use serenity::model::channel::Channel;
+use serenity::model::id::{ChannelId, GuildId, UserId};
+use serenity::model::permissions::Permissions;
+use serenity::model::prelude::command::CommandOptionType;
+use serenity::model::prelude::interaction::application_command::ApplicationCommandInteraction;
+use serenity::model::prelude::interaction::InteractionResponseType;
+use serenity::prelude::Context;
+use tantivy::collector::TopDocs;
+use tantivy::Document;
+
+use crate::data::shared_state;
+use crate::query::parse_query;
+use crate::schema::MessageSchema;
+
+pub const COMMAND_NAME: &str = "search";
+const RESULT_LIMIT: usize = 10;
+/// over-fetch hits since some will be dropped by the permission filter below
+const OVERFETCH_FACTOR: usize = 4;
+const CONTENT_PREVIEW_LEN: usize = 200;
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command
+        .name(COMMAND_NAME)
+        .description("Search this server's message history")
+        .create_option(|option| {
+            option
+                .name("query")
+                .description(
+                    "free text plus filters: from: in: has: mime: pinned: mentions: before: after:",
+                )
+                .kind(CommandOptionType::String)
+                .required(true)
+        })
+}
+
+struct SearchHit {
+    message_id: u64,
+    channel_id: u64,
+    content: String,
+}
+
+impl SearchHit {
+    fn from_document(schema: &MessageSchema, doc: &Document) -> Option<Self> {
+        Some(Self {
+            message_id: doc.get_first(schema.id_field())?.as_u64()?,
+            channel_id: doc.get_first(schema.channel_id_field())?.as_u64()?,
+            content: doc
+                .get_first(schema.content_field())?
+                .as_text()?
+                .to_owned(),
+        })
+    }
+}
+
+pub async fn run(ctx: &Context, command: &ApplicationCommandInteraction) {
+    let query_input = command
+        .data
+        .options
+        .first()
+        .and_then(|option| option.value.as_ref())
+        .and_then(|value| value.as_str())
+        .unwrap_or_default();
+
+    let Some(guild_id) = command.guild_id else {
+        respond(ctx, command, "/search only works in a server.").await;
+        return;
+    };
+
+    let (Some(schema), Some(index)) = shared_state(ctx).await else {
+        respond(ctx, command, "Search isn't ready yet, try again in a moment.").await;
+        return;
+    };
+
+    let query = parse_query(&schema, guild_id.0, query_input);
+    let searcher = index.searcher();
+    let top_docs = searcher
+        .search(&query, &TopDocs::with_limit(RESULT_LIMIT * OVERFETCH_FACTOR))
+        .expect("Failed to run search");
+
+    let mut hits = Vec::with_capacity(RESULT_LIMIT);
+    for (_score, doc_address) in top_docs {
+        if hits.len() >= RESULT_LIMIT {
+            break;
+        }
+
+        let doc = searcher.doc(doc_address).expect("Failed to fetch document");
+        let Some(hit) = SearchHit::from_document(&schema, &doc) else {
+            continue;
+        };
+
+        if can_view_channel(ctx, hit.channel_id, command.user.id).await {
+            hits.push(hit);
+        }
+    }
+
+    if hits.is_empty() {
+        respond(ctx, command, "No results found.").await;
+        return;
+    }
+
+    respond_with_results(ctx, command, guild_id, &hits).await;
+}
+
+/// a user can see a hit's channel only if they have `VIEW_CHANNEL` and
+/// `READ_MESSAGE_HISTORY` there; anything we can't resolve is treated as denied
+async fn can_view_channel(ctx: &Context, channel_id: u64, user_id: UserId) -> bool {
+    let channel_id = ChannelId(channel_id);
+
+    let channel = match ctx.cache.guild_channel(channel_id) {
+        Some(channel) => channel,
+        None => match ctx.http.get_channel(channel_id.0).await {
+            Ok(Channel::Guild(channel)) => channel,
+            _ => return false,
+        },
+    };
+
+    channel
+        .permissions_for_user(&ctx.cache, user_id)
+        .map(|permissions| {
+            permissions.contains(Permissions::VIEW_CHANNEL | Permissions::READ_MESSAGE_HISTORY)
+        })
+        .unwrap_or(false)
+}
+
+async fn respond_with_results(
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+    guild_id: GuildId,
+    hits: &[SearchHit],
+) {
+    if let Err(why) = command
+        .create_interaction_response(&ctx.http, |response| {
+            response
+                .kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|message| {
+                    message.embed(|embed| {
+                        embed.title("Search results");
+                        for hit in hits {
+                            embed.field(
+                                format!("<#{}>", hit.channel_id),
+                                format!(
+                                    "[{}](https://discord.com/channels/{guild_id}/{}/{})",
+                                    preview(&hit.content),
+                                    hit.channel_id,
+                                    hit.message_id,
+                                ),
+                                false,
+                            );
+                        }
+                        embed
+                    })
+                })
+        })
+        .await
+    {
+        println!("Failed to respond to /search: {why:?}");
+    }
+}
+
+fn preview(content: &str) -> String {
+    if content.chars().count() <= CONTENT_PREVIEW_LEN {
+        return content.to_owned();
+    }
+
+    let truncated: String = content.chars().take(CONTENT_PREVIEW_LEN).collect();
+    format!("{truncated}…")
+}
+
+async fn respond(ctx: &Context, command: &ApplicationCommandInteraction, content: &str) {
+    if let Err(why) = command
+        .create_interaction_response(&ctx.http, |response| {
+            response
+                .kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|message| message.content(content))
+        })
+        .await
+    {
+        println!("Failed to respond to /search: {why:?}");
+    }
+}
@@ -0,0 +1,139 @@
+//! the `/stats` slash command: media-type counts via a `FacetCollector`,
+//! optionally scoped to a channel and/or a timestamp range
+
+use serenity::builder::CreateApplicationCommand;
+use serenity::model::prelude::command::CommandOptionType;
+use serenity::model::prelude::interaction::application_command::{
+    ApplicationCommandInteraction, CommandDataOptionValue,
+};
+use serenity::model::prelude::interaction::InteractionResponseType;
+use serenity::prelude::Context;
+use tantivy::collector::FacetCollector;
+use tantivy::query::{BooleanQuery, Occur, Query};
+
+use crate::data::shared_state;
+use crate::query::{now_unix, term_query_u64, timestamp_range_query};
+
+pub const COMMAND_NAME: &str = "stats";
+const HAS_FACET: &str = "/has";
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command
+        .name(COMMAND_NAME)
+        .description("Show media-type counts for this server's indexed messages")
+        .create_option(|option| {
+            option
+                .name("channel")
+                .description("only count messages in this channel")
+                .kind(CommandOptionType::Channel)
+                .required(false)
+        })
+        .create_option(|option| {
+            option
+                .name("after")
+                .description("only count messages after this unix timestamp")
+                .kind(CommandOptionType::Integer)
+                .required(false)
+        })
+        .create_option(|option| {
+            option
+                .name("before")
+                .description("only count messages before this unix timestamp")
+                .kind(CommandOptionType::Integer)
+                .required(false)
+        })
+}
+
+pub async fn run(ctx: &Context, command: &ApplicationCommandInteraction) {
+    let Some(guild_id) = command.guild_id else {
+        respond(ctx, command, "/stats only works in a server.").await;
+        return;
+    };
+
+    let (Some(schema), Some(index)) = shared_state(ctx).await else {
+        respond(ctx, command, "Search isn't ready yet, try again in a moment.").await;
+        return;
+    };
+
+    // always scope to the invoking guild so a moderator in one server never
+    // sees media counts aggregated across every server the bot is in
+    let mut clauses: Vec<(Occur, Box<dyn Query>)> = vec![(
+        Occur::Must,
+        term_query_u64(schema.guild_id_field(), guild_id.0),
+    )];
+
+    if let Some(channel_id) = channel_option(command) {
+        clauses.push((Occur::Must, term_query_u64(schema.channel_id_field(), channel_id)));
+    }
+
+    let after = integer_option(command, "after");
+    let before = integer_option(command, "before");
+    if after.is_some() || before.is_some() {
+        let start = after.unwrap_or(0);
+        let end = before.unwrap_or_else(now_unix);
+        clauses.push((Occur::Must, timestamp_range_query(&schema, start, end)));
+    }
+
+    let query: Box<dyn Query> = Box::new(BooleanQuery::new(clauses));
+
+    let mut facet_collector = FacetCollector::for_field(schema.has_facet_field());
+    facet_collector.add_facet(HAS_FACET);
+
+    let searcher = index.searcher();
+    let counts = searcher
+        .search(&query, &facet_collector)
+        .expect("Failed to run facet search");
+
+    let mut lines: Vec<String> = counts
+        .get(HAS_FACET)
+        .map(|(facet, count)| {
+            let media_type = facet.to_path().last().copied().unwrap_or("?");
+            format!("{media_type}: {count}")
+        })
+        .collect();
+    lines.sort();
+
+    if lines.is_empty() {
+        respond(ctx, command, "No media found in matching messages.").await;
+        return;
+    }
+
+    respond(ctx, command, &lines.join("\n")).await;
+}
+
+fn channel_option(command: &ApplicationCommandInteraction) -> Option<u64> {
+    let option = command
+        .data
+        .options
+        .iter()
+        .find(|option| option.name == "channel")?;
+
+    match option.resolved.as_ref()? {
+        CommandDataOptionValue::Channel(channel) => Some(channel.id.0),
+        _ => None,
+    }
+}
+
+fn integer_option(command: &ApplicationCommandInteraction, name: &str) -> Option<i64> {
+    command
+        .data
+        .options
+        .iter()
+        .find(|option| option.name == name)?
+        .value
+        .as_ref()?
+        .as_i64()
+}
+
+async fn respond(ctx: &Context, command: &ApplicationCommandInteraction, content: &str) {
+    if let Err(why) = command
+        .create_interaction_response(&ctx.http, |response| {
+            response
+                .kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|message| message.content(content))
+        })
+        .await
+    {
+        println!("Failed to respond to /stats: {why:?}");
+    }
+}